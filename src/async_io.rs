@@ -0,0 +1,54 @@
+//! Async I/O for [Archive], gated behind the `tokio` feature.
+//!
+//! The parser itself always operates on a fully-buffered [String], so only
+//! the I/O boundary needs an async variant; these methods mirror
+//! [Archive::from_file], [Archive::read] and [Archive::write] one for one.
+
+use crate::Archive;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+impl Archive {
+    /// Read an archive from the file specified by `path`, asynchronously.
+    pub async fn from_file_async(path: &str) -> std::io::Result<Self> {
+        let mut f = tokio::fs::File::open(path).await?;
+        Archive::read_async(&mut f).await
+    }
+
+    /// Read an archive from the specified `reader`, asynchronously.
+    pub async fn read_async<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Self> {
+        let mut s = String::new();
+        reader.read_to_string(&mut s).await?;
+        Ok(Archive::from(s.as_str()))
+    }
+
+    /// Write `self` as txtar text to `writer`, asynchronously.
+    pub async fn write_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.format().as_bytes()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_async_matches_sync_parse() {
+        let tx_str = "comment\n-- file1 --\nhello\n";
+        let mut reader = tx_str.as_bytes();
+
+        let archive = Archive::read_async(&mut reader).await.unwrap();
+
+        assert_eq!(archive.comment, "comment\n");
+        assert_eq!(archive["file1"].content, "hello\n");
+    }
+
+    #[tokio::test]
+    async fn write_async_matches_format() {
+        let archive = Archive::from("-- file1 --\nhello\n");
+        let mut buf = Vec::new();
+
+        archive.write_async(&mut buf).await.unwrap();
+
+        assert_eq!(buf, archive.format().into_bytes());
+    }
+}
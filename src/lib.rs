@@ -63,7 +63,27 @@
 //! assert!(archive.get("not-exists").is_none());
 //!```
 
+#[cfg(test)]
+pub(crate) mod test_support {
+    /// A process-unique scratch directory under the OS temp dir, with any
+    /// leftovers from a previous run removed. Shared by the test modules
+    /// that need real filesystem paths (`lib.rs`, `testing.rs`).
+    pub fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("rs-txtar-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod async_io;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 #[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A txtar archive
 pub struct Archive {
     /// The comments from the archive
@@ -105,6 +125,149 @@ impl Archive {
     pub fn get(&self, name: &str) -> Option<&File> {
         self.files.iter().find(|f| f.name.as_str() == name)
     }
+
+    /// Format `self` back into txtar text. An archive produced by parsing re-parses to an equal archive.
+    /// File content that lacks a trailing newline gets one, so it re-parses with that newline added;
+    /// the comment and file names are written as they are.
+    pub fn format(&self) -> String {
+        let mut out = self.comment.clone();
+        for file in &self.files {
+            out.push_str(MARKER);
+            out.push_str(&file.name);
+            out.push_str(MARKER_END);
+            out.push('\n');
+            out.push_str(&fix_newline(&file.content));
+        }
+        out
+    }
+
+    /// Write `self` as txtar text to `writer`
+    pub fn write(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        writer.write_all(self.format().as_bytes())
+    }
+
+    /// Recursively walk `root` and build an archive whose file names are paths relative to `root`, using `/` separators.
+    pub fn from_dir(root: &std::path::Path) -> std::io::Result<Archive> {
+        Archive::from_dir_with(root, NonUtf8Policy::Error)
+    }
+
+    /// Like [Archive::from_dir], but `policy` controls how non-UTF-8 files are handled.
+    pub fn from_dir_with(
+        root: &std::path::Path,
+        policy: NonUtf8Policy,
+    ) -> std::io::Result<Archive> {
+        let mut files = Vec::new();
+        collect_dir(root, root, policy, &mut files)?;
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Archive {
+            comment: String::new(),
+            files,
+        })
+    }
+
+    /// Set the content of the file named `name` to `content`, replacing the
+    /// first existing file with that name if present, or appending a new
+    /// file otherwise.
+    pub fn set(&mut self, name: &str, content: &str) {
+        match self.files.iter_mut().find(|f| f.name == name) {
+            Some(file) => file.content = content.to_owned(),
+            None => self.files.push(File::new(name, content)),
+        }
+    }
+
+    /// Remove the first file named `name` from the archive, returning it if
+    /// it was present.
+    pub fn remove(&mut self, name: &str) -> Option<File> {
+        let index = self.files.iter().position(|f| f.name == name)?;
+        Some(self.files.remove(index))
+    }
+
+    /// Write every file in `self` into a directory tree rooted at `root`, creating intermediate directories as needed.
+    pub fn extract_to(&self, root: &std::path::Path) -> std::io::Result<()> {
+        for file in &self.files {
+            let path = safe_join(root, &file.name)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &file.content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Controls how [Archive::from_dir_with] handles files that aren't valid
+/// UTF-8 text.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonUtf8Policy {
+    /// Return an error if a file isn't valid UTF-8.
+    Error,
+    /// Silently skip files that aren't valid UTF-8.
+    Skip,
+}
+
+/// Recursively walks `dir` (a descendant of, or equal to, `root`), appending
+/// a [File] for each text file found to `files`.
+fn collect_dir(
+    root: &std::path::Path,
+    dir: &std::path::Path,
+    policy: NonUtf8Policy,
+    files: &mut Vec<File>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_dir(root, &path, policy, files)?;
+            continue;
+        }
+
+        let bytes = std::fs::read(&path)?;
+        let content = match (String::from_utf8(bytes), policy) {
+            (Ok(content), _) => content,
+            (Err(_), NonUtf8Policy::Skip) => continue,
+            (Err(e), NonUtf8Policy::Error) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                ))
+            }
+        };
+
+        let rel = path.strip_prefix(root).unwrap();
+        let name = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push(File::new(&name, &content));
+    }
+    Ok(())
+}
+
+/// Joins `name` onto `root`, rejecting any component that would let `name`
+/// escape `root` (an absolute path or a `..` component).
+fn safe_join(root: &std::path::Path, name: &str) -> std::io::Result<std::path::PathBuf> {
+    let mut joined = root.to_path_buf();
+    for component in std::path::Path::new(name).components() {
+        match component {
+            std::path::Component::Normal(part) => joined.push(part),
+            std::path::Component::CurDir => {}
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("file name escapes extraction root: {}", name),
+                ))
+            }
+        }
+    }
+    Ok(joined)
+}
+
+/// Formats `self` as txtar text. See [Archive::format].
+impl std::fmt::Display for Archive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format())
+    }
 }
 
 impl Default for Archive {
@@ -151,8 +314,21 @@ impl std::ops::Index<&str> for Archive {
     }
 }
 
+impl std::ops::IndexMut<&str> for Archive {
+    /// Return the file named `index` mutably, or panics if there is no such
+    /// file. See [Archive::set] to insert a file instead of panicking.
+    fn index_mut(&mut self, index: &str) -> &mut Self::Output {
+        match self.files.iter_mut().find(|f| f.name.as_str() == index) {
+            Some(f) => f,
+            None => panic!("Archive doesn't contain file: {}", index),
+        }
+    }
+}
+
 /// A file that resides in a txtar [Archive]
 #[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct File {
     /// The name of the file
     pub name: String,
@@ -292,4 +468,196 @@ hello world",
             ]
         }
     );
+
+    #[test]
+    fn format_round_trips() {
+        let tx_str = "comment1\ncomment2\n-- file1 --\nFile 1 text.\n-- file 2 --\nFile 2 text.\n";
+        let archive = Archive::from(tx_str);
+        let formatted = archive.format();
+
+        assert_eq!(formatted, tx_str);
+
+        let reparsed = Archive::from(formatted.as_str());
+        assert_eq!(reparsed.comment, archive.comment);
+        assert_eq!(reparsed.files.len(), archive.files.len());
+        for (a, b) in reparsed.files.iter().zip(archive.files.iter()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.content, b.content);
+        }
+    }
+
+    #[test]
+    fn format_adds_missing_trailing_newline() {
+        let mut archive = Archive::new();
+        archive.files.push(File::new("noNL", "hello world"));
+
+        assert_eq!(archive.format(), "-- noNL --\nhello world\n");
+    }
+
+    #[test]
+    fn display_matches_format() {
+        let archive = Archive::from("-- a --\nhello\n");
+        assert_eq!(archive.to_string(), archive.format());
+    }
+
+    use test_support::temp_dir;
+
+    #[test]
+    fn extract_to_writes_files_and_creates_dirs() {
+        let root = temp_dir("extract-ok");
+        let archive = Archive::from("-- a.txt --\nhello\n-- sub/b.txt --\nworld\n");
+
+        archive.extract_to(&root).unwrap();
+
+        assert_eq!(std::fs::read_to_string(root.join("a.txt")).unwrap(), "hello\n");
+        assert_eq!(
+            std::fs::read_to_string(root.join("sub/b.txt")).unwrap(),
+            "world\n"
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn extract_to_rejects_path_traversal() {
+        let root = temp_dir("extract-traversal");
+        let archive = Archive::from("-- ../escape.txt --\nhello\n");
+
+        let err = archive.extract_to(&root).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!root.with_file_name("escape.txt").exists());
+    }
+
+    #[test]
+    fn extract_to_rejects_absolute_names() {
+        let root = temp_dir("extract-absolute");
+        let archive = Archive::from("-- /etc/escape.txt --\nhello\n");
+
+        let err = archive.extract_to(&root).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn from_dir_reads_nested_text_files() {
+        let root = temp_dir("from-dir-ok");
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a.txt"), "hello\n").unwrap();
+        std::fs::write(root.join("sub/b.txt"), "world").unwrap();
+
+        let archive = Archive::from_dir(&root).unwrap();
+
+        assert_eq!(archive.files.len(), 2);
+        assert_eq!(archive["a.txt"].content, "hello\n");
+        assert_eq!(archive["sub/b.txt"].content, "world");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn from_dir_errors_on_non_utf8_by_default() {
+        let root = temp_dir("from-dir-binary");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("bin"), [0xff, 0xfe, 0x00, 0xff]).unwrap();
+
+        let err = Archive::from_dir(&root).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn from_dir_with_skip_ignores_non_utf8() {
+        let root = temp_dir("from-dir-skip");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("bin"), [0xff, 0xfe, 0x00, 0xff]).unwrap();
+        std::fs::write(root.join("text.txt"), "ok\n").unwrap();
+
+        let archive = Archive::from_dir_with(&root, NonUtf8Policy::Skip).unwrap();
+
+        assert_eq!(archive.files.len(), 1);
+        assert_eq!(archive["text.txt"].content, "ok\n");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn from_dir_round_trips_with_extract_to() {
+        let src = temp_dir("from-dir-src");
+        let dst = temp_dir("from-dir-dst");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        std::fs::write(src.join("a.txt"), "hello\n").unwrap();
+        std::fs::write(src.join("sub/b.txt"), "world\n").unwrap();
+
+        let archive = Archive::from_dir(&src).unwrap();
+        archive.extract_to(&dst).unwrap();
+
+        assert_eq!(std::fs::read_to_string(dst.join("a.txt")).unwrap(), "hello\n");
+        assert_eq!(
+            std::fs::read_to_string(dst.join("sub/b.txt")).unwrap(),
+            "world\n"
+        );
+
+        std::fs::remove_dir_all(&src).unwrap();
+        std::fs::remove_dir_all(&dst).unwrap();
+    }
+
+    #[test]
+    fn set_replaces_first_match() {
+        let mut archive = Archive::from("-- a --\none\n-- a --\ntwo\n");
+
+        archive.set("a", "three");
+
+        assert_eq!(archive.files.len(), 2);
+        assert_eq!(archive.files[0].content, "three");
+        assert_eq!(archive.files[1].content, "two\n");
+    }
+
+    #[test]
+    fn set_appends_when_missing() {
+        let mut archive = Archive::new();
+
+        archive.set("a", "hello");
+
+        assert_eq!(archive.files.len(), 1);
+        assert_eq!(archive["a"].content, "hello");
+    }
+
+    #[test]
+    fn remove_returns_first_match() {
+        let mut archive = Archive::from("-- a --\none\n-- b --\ntwo\n");
+
+        let removed = archive.remove("a").unwrap();
+
+        assert_eq!(removed.content, "one\n");
+        assert!(!archive.contains("a"));
+        assert!(archive.contains("b"));
+    }
+
+    #[test]
+    fn remove_returns_none_when_missing() {
+        let mut archive = Archive::new();
+
+        assert!(archive.remove("a").is_none());
+    }
+
+    #[test]
+    fn index_mut_allows_in_place_edits() {
+        let mut archive = Archive::from("-- a --\nhello\n");
+
+        archive["a"].content = "world".to_owned();
+
+        assert_eq!(archive["a"].content, "world");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips_through_json() {
+        let archive = Archive::from("comment\n-- a --\nhello\n-- b --\nworld\n");
+
+        let json = serde_json::to_string(&archive).unwrap();
+        let round_tripped: Archive = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(round_tripped.comment, archive.comment);
+        assert_eq!(round_tripped.format(), archive.format());
+    }
 }
@@ -0,0 +1,141 @@
+//! A golden-file test harness for crates that drive their tests from txtar
+//! fixtures, gated behind the `testing` feature.
+//!
+//! [dir_tests] walks a directory of `.txtar` fixtures, hands each one's files
+//! to a user-supplied closure, and asserts the resulting [Archive] matches a
+//! checked-in `*.expected.<extension>` sibling file via [expect_file].
+//! Setting the `UPDATE_EXPECT` environment variable regenerates the
+//! expectations on disk instead of asserting, the usual workflow for
+//! reviewing and accepting a batch of golden-file changes.
+
+use crate::Archive;
+use std::path::Path;
+
+/// Walk `dir` for fixtures ending in `.<extension>` (skipping any
+/// `*.expected.<extension>` files, which hold the golden output), run each
+/// one's files through `f`, and assert the resulting archive matches the
+/// `<name>.expected.<extension>` sibling via [expect_file].
+///
+/// Panics if `dir` can't be read or if any fixture fails to match its
+/// expectation.
+pub fn dir_tests(dir: &Path, extension: &str, f: impl Fn(&[crate::File]) -> Archive) {
+    let update = std::env::var_os("UPDATE_EXPECT").is_some();
+    let suffix = format!(".{}", extension);
+    let expected_suffix = format!(".expected.{}", extension);
+
+    let entries = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("reading fixture dir {}: {}", dir.display(), e));
+
+    for entry in entries {
+        let path = entry.unwrap_or_else(|e| panic!("reading fixture entry: {}", e)).path();
+        let file_name = path.file_name().unwrap().to_string_lossy();
+
+        if !file_name.ends_with(&suffix) || file_name.ends_with(&expected_suffix) {
+            continue;
+        }
+
+        let input = Archive::from_file(path.to_str().unwrap())
+            .unwrap_or_else(|e| panic!("reading fixture {}: {}", path.display(), e));
+        let actual = f(&input.files);
+
+        let expected_name = format!(
+            "{}{}",
+            &file_name[..file_name.len() - suffix.len()],
+            expected_suffix
+        );
+        let expected_path = path.with_file_name(expected_name);
+
+        expect_file_with(&expected_path, &actual, update);
+    }
+}
+
+/// Assert that `actual` matches the txtar archive checked in at `path`.
+///
+/// If `UPDATE_EXPECT` is set in the environment (to any value), `path` is
+/// (re)written with `actual` instead of being compared against, which is how
+/// golden files are regenerated.
+pub fn expect_file(path: &Path, actual: &Archive) {
+    let update = std::env::var_os("UPDATE_EXPECT").is_some();
+    expect_file_with(path, actual, update)
+}
+
+/// Does the comparison (or regeneration) for [expect_file] and [dir_tests],
+/// taking `update` as an explicit argument so callers only read the
+/// `UPDATE_EXPECT` environment variable once, rather than racing on it.
+fn expect_file_with(path: &Path, actual: &Archive, update: bool) {
+    if update {
+        std::fs::write(path, actual.format())
+            .unwrap_or_else(|e| panic!("writing expectation {}: {}", path.display(), e));
+        return;
+    }
+
+    let expected = Archive::from_file(path.to_str().unwrap()).unwrap_or_else(|e| {
+        panic!(
+            "reading expectation {}: {} (rerun with UPDATE_EXPECT=1 to create it)",
+            path.display(),
+            e
+        )
+    });
+
+    assert_eq!(
+        actual.format(),
+        expected.format(),
+        "{} does not match expectation; rerun with UPDATE_EXPECT=1 to update it",
+        path.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::temp_dir;
+
+    fn upper_case(files: &[crate::File]) -> Archive {
+        Archive {
+            comment: String::new(),
+            files: files
+                .iter()
+                .map(|f| crate::File::new(&f.name, &f.content.to_uppercase()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn dir_tests_passes_when_expectation_matches() {
+        let dir = temp_dir("dir-tests-pass");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("basic.txtar"), "-- a --\nhello\n").unwrap();
+        std::fs::write(dir.join("basic.expected.txtar"), "-- a --\nHELLO\n").unwrap();
+
+        dir_tests(&dir, "txtar", upper_case);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match expectation")]
+    fn dir_tests_panics_on_mismatch() {
+        let dir = temp_dir("dir-tests-mismatch");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("basic.txtar"), "-- a --\nhello\n").unwrap();
+        std::fs::write(dir.join("basic.expected.txtar"), "-- a --\nnope\n").unwrap();
+
+        dir_tests(&dir, "txtar", upper_case);
+    }
+
+    #[test]
+    fn expect_file_regenerates_when_update_is_true() {
+        let dir = temp_dir("expect-file-update");
+        std::fs::create_dir_all(&dir).unwrap();
+        let expected_path = dir.join("basic.expected.txtar");
+        let actual = Archive::from("-- a --\nHELLO\n");
+
+        expect_file_with(&expected_path, &actual, true);
+
+        let written = std::fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(written, actual.format());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}